@@ -27,68 +27,111 @@ use crate::core::traffic_gen_core::types::{Encapsulation, GenerationMode};
 /// Checks if the MPLS/SRv6 configuration is correct, i.e., if the MPLS stack matches the number of LSEs.
 pub fn validate_request(streams: &[Stream], settings: &[StreamSetting], mode: &GenerationMode, is_tofino2: bool) -> Result<(), Error> {
     for stream in streams.iter(){
-        // Check max number of MPLS labels
-        if stream.encapsulation == Encapsulation::Mpls {
-            if stream.number_of_lse.is_none() {
-                return Err(Error::new(format!("number_of_lse missing for stream #{}", stream.stream_id)))
-            }
+        // Walk the encapsulation stack (outer -> inner) and validate each layer.
+        for encapsulation in stream.encapsulation.iter() {
+            // Check max number of MPLS labels
+            if *encapsulation == Encapsulation::Mpls {
+                if stream.number_of_lse.is_none() {
+                    return Err(Error::new(format!("number_of_lse missing for stream #{}", stream.stream_id)))
+                }
 
-            if stream.number_of_lse.unwrap() > MAX_NUM_MPLS_LABEL {
-                return Err(Error::new(format!("Configured number of LSEs in stream with ID #{} exceeded maximum of {}.", stream.stream_id, MAX_NUM_MPLS_LABEL)));
-            }
+                if stream.number_of_lse.unwrap() > MAX_NUM_MPLS_LABEL {
+                    return Err(Error::new(format!("Configured number of LSEs in stream with ID #{} exceeded maximum of {}.", stream.stream_id, MAX_NUM_MPLS_LABEL)));
+                }
 
-            if stream.number_of_lse.unwrap() == 0 {
-                return Err(Error::new(format!("MPLS encapsulation selected for stream with ID #{} but #LSE is zero.", stream.stream_id)));
-            }
-        } else if stream.encapsulation == Encapsulation::SRv6 {
-            if !is_tofino2 {
-                return Err(Error::new(format!("SRv6 is only supported on Tofino2.")));
-            }
+                if stream.number_of_lse.unwrap() == 0 {
+                    return Err(Error::new(format!("MPLS encapsulation selected for stream with ID #{} but #LSE is zero.", stream.stream_id)));
+                }
+            } else if *encapsulation == Encapsulation::SRv6 {
+                if !is_tofino2 {
+                    return Err(Error::new(format!("SRv6 is only supported on Tofino2.")));
+                }
 
-            if stream.number_of_srv6_sids.is_none() {
-                return Err(Error::new(format!("number_of_srv6_sids missing for stream #{}", stream.stream_id)))
-            }
+                if stream.number_of_srv6_sids.is_none() {
+                    return Err(Error::new(format!("number_of_srv6_sids missing for stream #{}", stream.stream_id)))
+                }
+
+                if stream.number_of_srv6_sids.unwrap() > MAX_NUM_SRV6_SIDS {
+                    return Err(Error::new(format!("Configured number of SIDs in stream with ID #{} exceeded maximum of {}.", stream.stream_id, MAX_NUM_SRV6_SIDS)));
+                }
 
-            if stream.number_of_srv6_sids.unwrap() > MAX_NUM_SRV6_SIDS {
-                return Err(Error::new(format!("Configured number of SIDs in stream with ID #{} exceeded maximum of {}.", stream.stream_id, MAX_NUM_SRV6_SIDS)));
+                if stream.number_of_srv6_sids.unwrap() == 0 {
+                    return Err(Error::new(format!("SRv6 encapsulation selected for stream with ID #{} but #SIDs is zero.", stream.stream_id)));
+                }
             }
+        }
+
+        // Validate the two-rate three-color profile of a trTCM stream.
+        if *mode == GenerationMode::TrTCM {
+            match &stream.trtcm {
+                None => return Err(Error::new(format!("GenerationMode trTCM selected but no trTCM profile provided for stream with ID #{}.", stream.stream_id))),
+                Some(profile) => {
+                    if profile.cir == 0 || profile.pir == 0 {
+                        return Err(Error::new(format!("CIR and PIR must be non-zero for stream with ID #{}.", stream.stream_id)));
+                    }
+
+                    if profile.cbs == 0 || profile.pbs == 0 {
+                        return Err(Error::new(format!("CBS and PBS must be non-zero for stream with ID #{}.", stream.stream_id)));
+                    }
 
-            if stream.number_of_srv6_sids.unwrap() == 0 {
-                return Err(Error::new(format!("SRv6 encapsulation selected for stream with ID #{} but #SIDs is zero.", stream.stream_id)));
+                    if profile.pir < profile.cir {
+                        return Err(Error::new(format!("PIR must be larger than or equal to CIR for stream with ID #{}.", stream.stream_id)));
+                    }
+
+                    if profile.pbs < profile.cbs {
+                        return Err(Error::new(format!("PBS must be larger than or equal to CBS for stream with ID #{}.", stream.stream_id)));
+                    }
+
+                    if let Some(dscp) = profile.yellow_dscp {
+                        if dscp > 63 {
+                            return Err(Error::new(format!("Yellow DSCP value {} out of range (0-63) for stream with ID #{}.", dscp, stream.stream_id)));
+                        }
+                    }
+
+                    if let Some(pcp) = profile.yellow_pcp {
+                        if pcp > 7 {
+                            return Err(Error::new(format!("Yellow PCP value {} out of range (0-7) for stream with ID #{}.", pcp, stream.stream_id)));
+                        }
+                    }
+                }
             }
         }
 
         for setting in settings.iter() {
             if setting.stream_id == stream.stream_id {
-                // check VLAN settings
-                if (stream.encapsulation == Encapsulation::Vlan || stream.encapsulation == Encapsulation::QinQ) && setting.vlan.is_none() {
-                    return Err(Error::new(format!("VLAN encapsulation selected for stream with iD #{}, but no VLAN settings provided for port {}.", stream.stream_id, setting.port)))
-                }
+                // Walk the encapsulation stack and verify each layer carries the
+                // per-port settings it requires (VLAN tag, MPLS stack, SID list).
+                for encapsulation in stream.encapsulation.iter() {
+                    // check VLAN settings
+                    if (*encapsulation == Encapsulation::Vlan || *encapsulation == Encapsulation::QinQ) && setting.vlan.is_none() {
+                        return Err(Error::new(format!("VLAN encapsulation selected for stream with iD #{}, but no VLAN settings provided for port {}.", stream.stream_id, setting.port)))
+                    }
 
-                // check MPLS
-                // check that mpls stack is set
-                if stream.encapsulation == Encapsulation::Mpls && setting.mpls_stack.is_none() {
-                    return Err(Error::new(format!("No MPLS stack provided for stream with ID #{} on port {}.", stream.stream_id, setting.port)))
-                }
+                    // check MPLS
+                    // check that mpls stack is set
+                    if *encapsulation == Encapsulation::Mpls && setting.mpls_stack.is_none() {
+                        return Err(Error::new(format!("No MPLS stack provided for stream with ID #{} on port {}.", stream.stream_id, setting.port)))
+                    }
 
-                // Validate if the configured number_of_lse per stream matches the MPLS stack size
-                if stream.encapsulation == Encapsulation::Mpls && setting.mpls_stack.as_ref().unwrap().len() != stream.number_of_lse.unwrap() as usize {
-                    return Err(Error::new(format!("Number of LSEs in stream with ID #{} does not match length of the MPLS stack.", setting.stream_id)));
-                }
+                    // Validate if the configured number_of_lse per stream matches the MPLS stack size
+                    if *encapsulation == Encapsulation::Mpls && setting.mpls_stack.as_ref().unwrap().len() != stream.number_of_lse.unwrap() as usize {
+                        return Err(Error::new(format!("Number of LSEs in stream with ID #{} does not match length of the MPLS stack.", setting.stream_id)));
+                    }
 
-                // check SRv6
-                // check that SID list is set
-                if stream.encapsulation == Encapsulation::SRv6 && setting.sid_list.is_none() {
-                    return Err(Error::new(format!("No SID list provided for stream with ID #{} on port {}.", stream.stream_id, setting.port)))
-                }
+                    // check SRv6
+                    // check that SID list is set
+                    if *encapsulation == Encapsulation::SRv6 && setting.sid_list.is_none() {
+                        return Err(Error::new(format!("No SID list provided for stream with ID #{} on port {}.", stream.stream_id, setting.port)))
+                    }
 
-                // Validate if the configured number_of_srv6_sids per stream matches the SID list length
-                if stream.encapsulation == Encapsulation::SRv6 && setting.sid_list.as_ref().unwrap().len() != stream.number_of_srv6_sids.unwrap() as usize {
-                    return Err(Error::new(format!("Number of SIDs in stream with ID #{} does not match length of the SID list.", setting.stream_id)));
+                    // Validate if the configured number_of_srv6_sids per stream matches the SID list length
+                    if *encapsulation == Encapsulation::SRv6 && setting.sid_list.as_ref().unwrap().len() != stream.number_of_srv6_sids.unwrap() as usize {
+                        return Err(Error::new(format!("Number of SIDs in stream with ID #{} does not match length of the SID list.", setting.stream_id)));
+                    }
                 }
 
                 // Validate IP settings, but not if no inner IP header is used in SRv6
-                if (stream.encapsulation == Encapsulation::SRv6 && stream.srv6_ip_tunneling.unwrap_or(true)) || stream.encapsulation != Encapsulation::SRv6 {
+                if !stream.encapsulation.contains(&Encapsulation::SRv6) || stream.srv6_ip_tunneling.unwrap_or(true) {
                     if stream.ip_version != Some(6) && stream.ip_version != Some(4) && !stream.ip_version.is_none() {
                         return Err(Error::new(format!("Unsupported IP version for stream with ID #{} on port {}.", stream.stream_id, setting.port)));
                     }
@@ -98,29 +141,135 @@ pub fn validate_request(streams: &[Stream], settings: &[StreamSetting], mode: &G
 
                     } else if stream.ip_version == Some(6) && setting.ipv6.is_none() {
                         return Err(Error::new(format!("Missing IPv6 settings for stream with ID #{} on port {}.", stream.stream_id, setting.port)));
-                    } 
+                    }
                 }
 
+                // Validate differentiated-services / TTL / flow-label fields.
+                // These describe an IP header, so they require a valid IP version and
+                // are meaningless on a headerless SRv6 stream (no inner IP header).
+                let headerless_srv6 = stream.encapsulation.contains(&Encapsulation::SRv6) && !stream.srv6_ip_tunneling.unwrap_or(true);
+                let has_dsfield = stream.dscp.is_some() || stream.ecn.is_some() || stream.ip_ttl.is_some() || stream.hop_limit.is_some() || stream.flow_label.is_some();
 
-            }
+                if has_dsfield && (stream.ip_version.is_none() || headerless_srv6) {
+                    return Err(Error::new(format!("DSCP/ECN/TTL/flow-label configured for stream with ID #{} but the stream carries no IP header.", stream.stream_id)));
+                }
 
-            // Check VxLAN
-            if stream.vxlan && setting.vxlan.is_none() {
-                return Err(Error::new(format!("Stream with ID #{} is a VxLAN stream but no VxLAN settings provided.", stream.stream_id)));
-            }
+                if let Some(dscp) = stream.dscp {
+                    if dscp > 63 {
+                        return Err(Error::new(format!("DSCP value {} out of range (0-63) for stream with ID #{}.", dscp, stream.stream_id)));
+                    }
+                }
 
-            if stream.vxlan && stream.ip_version == Some(6) {
-                return Err(Error::new(format!("VxLAN with IPv6 is not supported! (Stream with ID #{})", stream.stream_id)));
-            }
+                if let Some(ecn) = stream.ecn {
+                    if ecn > 3 {
+                        return Err(Error::new(format!("ECN value {} out of range (0-3) for stream with ID #{}.", ecn, stream.stream_id)));
+                    }
+                }
+
+                if let Some(ttl) = stream.ip_ttl {
+                    if ttl == 0 {
+                        return Err(Error::new(format!("TTL value {} out of range (1-255) for stream with ID #{}.", ttl, stream.stream_id)));
+                    }
+                }
+
+                if let Some(hop_limit) = stream.hop_limit {
+                    if hop_limit == 0 {
+                        return Err(Error::new(format!("Hop-limit value {} out of range (1-255) for stream with ID #{}.", hop_limit, stream.stream_id)));
+                    }
+                }
+
+                if let Some(flow_label) = stream.flow_label {
+                    if flow_label >= (1 << 20) {
+                        return Err(Error::new(format!("Flow-label value {} out of range (0-{}) for stream with ID #{}.", flow_label, (1u32 << 20) - 1, stream.stream_id)));
+                    }
+                }
+
+                // For VxLAN streams the outer header may carry DSCP/TTL values
+                // independent of the inner IP header; validate them as well.
+                if stream.vxlan {
+                    if let Some(dscp) = stream.vxlan_dscp {
+                        if dscp > 63 {
+                            return Err(Error::new(format!("Outer VxLAN DSCP value {} out of range (0-63) for stream with ID #{}.", dscp, stream.stream_id)));
+                        }
+                    }
+
+                    if let Some(ttl) = stream.vxlan_ttl {
+                        if ttl == 0 {
+                            return Err(Error::new(format!("Outer VxLAN TTL value {} out of range (1-255) for stream with ID #{}.", ttl, stream.stream_id)));
+                        }
+                    }
+                }
+
+                // Validate the multicast replicas of this stream. Each replica is
+                // emitted to a distinct egress port with its own per-replica rewrites.
+                if !setting.replicas.is_empty() {
+                    let mut seen_ports: Vec<u32> = vec![setting.port];
+
+                    for replica in setting.replicas.iter() {
+                        if seen_ports.contains(&replica.port) {
+                            return Err(Error::new(format!("Duplicate replica egress port {} for stream with ID #{}.", replica.port, stream.stream_id)));
+                        }
+                        seen_ports.push(replica.port);
+
+                        // Each replica rewrites the destination MAC per egress port.
+                        if replica.eth_dst.is_none() {
+                            return Err(Error::new(format!("Replica on port {} for stream with ID #{} is missing a destination MAC rewrite.", replica.port, stream.stream_id)));
+                        }
+
+                        // A VLAN-encapsulated stream requires a per-replica VLAN rewrite.
+                        if (stream.encapsulation.contains(&Encapsulation::Vlan) || stream.encapsulation.contains(&Encapsulation::QinQ)) && replica.vlan.is_none() {
+                            return Err(Error::new(format!("Replica on port {} for stream with ID #{} is missing a VLAN rewrite.", replica.port, stream.stream_id)));
+                        }
+
+                        // An MPLS-encapsulated stream requires a per-replica label stack
+                        // rewrite, matching the stream's configured number of LSEs.
+                        if stream.encapsulation.contains(&Encapsulation::Mpls) {
+                            match replica.mpls_stack.as_ref() {
+                                None => return Err(Error::new(format!("Replica on port {} for stream with ID #{} is missing an MPLS stack rewrite.", replica.port, stream.stream_id))),
+                                Some(mpls_stack) if mpls_stack.len() != stream.number_of_lse.unwrap_or(0) as usize => {
+                                    return Err(Error::new(format!("Replica on port {} for stream with ID #{}: MPLS stack rewrite does not match the configured number of LSEs.", replica.port, stream.stream_id)));
+                                }
+                                _ => {}
+                            }
+                        }
+
+                        // An SRv6-encapsulated stream requires a per-replica SID list
+                        // rewrite, matching the stream's configured number of SIDs.
+                        if stream.encapsulation.contains(&Encapsulation::SRv6) {
+                            match replica.sid_list.as_ref() {
+                                None => return Err(Error::new(format!("Replica on port {} for stream with ID #{} is missing a SID list rewrite.", replica.port, stream.stream_id))),
+                                Some(sid_list) if sid_list.len() != stream.number_of_srv6_sids.unwrap_or(0) as usize => {
+                                    return Err(Error::new(format!("Replica on port {} for stream with ID #{}: SID list rewrite does not match the configured number of SIDs.", replica.port, stream.stream_id)));
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+
+                // Check VxLAN
+                if stream.vxlan && setting.vxlan.is_none() {
+                    return Err(Error::new(format!("Stream with ID #{} is a VxLAN stream but no VxLAN settings provided.", stream.stream_id)));
+                }
 
-            // Check VxLAN is disabled for SRv6
-            if stream.vxlan && stream.encapsulation == Encapsulation::SRv6 {
-                return Err(Error::new(format!("Combination of VxLAN and SRv6 is not supported (Stream with ID #{})", stream.stream_id)));
-            }                     
+                // The VxLAN outer header may be IPv4 or IPv6, independent of the inner
+                // payload's IP version. Validate the outer addresses of the selected family.
+                if stream.vxlan {
+                    if let Some(vxlan) = setting.vxlan.as_ref() {
+                        if stream.vxlan_outer_ipv6 {
+                            if vxlan.ipv6_src.is_none() || vxlan.ipv6_dst.is_none() {
+                                return Err(Error::new(format!("VXLANv6 stream with ID #{} is missing an IPv6 outer source/destination.", stream.stream_id)));
+                            }
+                        } else if vxlan.ip_src.is_none() || vxlan.ip_dst.is_none() {
+                            return Err(Error::new(format!("VxLAN stream with ID #{} is missing an IPv4 outer source/destination.", stream.stream_id)));
+                        }
+                    }
+                }
+            }
         }
     }
 
-    if streams.iter().map(|s| s.frame_size).collect::<Vec<u32>>().iter().sum::<u32>() > MAX_BUFFER_SIZE {
+    if streams.iter().map(|s| s.frame_size + calculate_overhead(s)).sum::<u32>() > MAX_BUFFER_SIZE {
         return Err(Error::new(format!("Sum of packet size too large. Maximal sum of packets size: {}B", MAX_BUFFER_SIZE)));
     }
 
@@ -132,13 +281,29 @@ pub fn validate_request(streams: &[Stream], settings: &[StreamSetting], mode: &G
         return Err(Error::new("No stream provided."));
     }
 
+    // A multicast stream is emitted on its primary egress port plus one additional
+    // copy per replica port, so its contribution to the line-rate budget scales with
+    // 1 (the primary) + the number of replica ports.
+    let replica_factor = |stream: &Stream| -> f32 {
+        let replicas: usize = settings.iter()
+            .filter(|s| s.stream_id == stream.stream_id)
+            .map(|s| s.replicas.len())
+            .sum();
+        (1 + replicas) as f32
+    };
+
     // Validate max sending rate
     // at most 100 or 400 Gbps are supported
     let rate: f32 = if *mode == GenerationMode::Mpps {
-        streams.iter().map(|x| (x.frame_size + calculate_overhead(x) + 20) as f32 * 8f32 * x.traffic_rate / 1000f32).sum()
+        streams.iter().map(|x| (x.frame_size + calculate_overhead(x) + 20) as f32 * 8f32 * x.traffic_rate / 1000f32 * replica_factor(x)).sum()
+    }
+    else if *mode == GenerationMode::TrTCM {
+        // A trTCM stream bursts up to its peak information rate, so the ceiling
+        // check must be done against PIR (in Gbit/s) rather than the flat rate.
+        streams.iter().map(|x| x.trtcm.as_ref().map(|p| p.pir as f32 / 1_000_000_000f32).unwrap_or(x.traffic_rate) * replica_factor(x)).sum()
     }
     else {
-        streams.iter().map(|x| x.traffic_rate).sum()
+        streams.iter().map(|x| x.traffic_rate * replica_factor(x)).sum()
     };
 
     if *mode != GenerationMode::Analyze && rate > if is_tofino2 {TG_MAX_RATE_TF2} else {TG_MAX_RATE} {
@@ -147,4 +312,424 @@ pub fn validate_request(streams: &[Stream], settings: &[StreamSetting], mode: &G
 
     Ok(())
 
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal stream with an IPv4 inner header, valid unless a test overrides a field.
+    fn base_stream() -> Stream {
+        Stream {
+            stream_id: 1,
+            frame_size: 64,
+            traffic_rate: 1f32,
+            encapsulation: vec![],
+            number_of_lse: None,
+            number_of_srv6_sids: None,
+            srv6_ip_tunneling: None,
+            ip_version: Some(4),
+            vxlan: false,
+            dscp: None,
+            ecn: None,
+            ip_ttl: None,
+            hop_limit: None,
+            flow_label: None,
+            vxlan_dscp: None,
+            vxlan_ttl: None,
+            vxlan_outer_ipv6: false,
+            trtcm: None,
+        }
+    }
+
+    /// The matching per-port setting for `base_stream()`.
+    fn base_setting() -> StreamSetting {
+        StreamSetting {
+            stream_id: 1,
+            port: 0,
+            vlan: None,
+            mpls_stack: None,
+            sid_list: None,
+            ip: Some(Ipv4Header { ip_src: "10.0.0.1".to_string(), ip_dst: "10.0.0.2".to_string() }),
+            ipv6: None,
+            vxlan: None,
+            replicas: vec![],
+            active: true,
+        }
+    }
+
+    #[test]
+    fn rejects_dscp_out_of_range() {
+        let mut stream = base_stream();
+        stream.dscp = Some(64);
+
+        assert!(validate_request(&[stream], &[base_setting()], &GenerationMode::Cbr, false).is_err());
+    }
+
+    #[test]
+    fn accepts_dscp_boundary_value() {
+        let mut stream = base_stream();
+        stream.dscp = Some(63);
+
+        assert!(validate_request(&[stream], &[base_setting()], &GenerationMode::Cbr, false).is_ok());
+    }
+
+    #[test]
+    fn rejects_ecn_out_of_range() {
+        let mut stream = base_stream();
+        stream.ecn = Some(4);
+
+        assert!(validate_request(&[stream], &[base_setting()], &GenerationMode::Cbr, false).is_err());
+    }
+
+    #[test]
+    fn rejects_zero_ttl() {
+        let mut stream = base_stream();
+        stream.ip_ttl = Some(0);
+
+        assert!(validate_request(&[stream], &[base_setting()], &GenerationMode::Cbr, false).is_err());
+    }
+
+    #[test]
+    fn rejects_zero_hop_limit() {
+        let mut stream = base_stream();
+        stream.ip_version = Some(6);
+        stream.hop_limit = Some(0);
+        let mut setting = base_setting();
+        setting.ip = None;
+        setting.ipv6 = Some(Ipv6Header { ipv6_src: "::1".to_string(), ipv6_dst: "::2".to_string() });
+
+        assert!(validate_request(&[stream], &[setting], &GenerationMode::Cbr, false).is_err());
+    }
+
+    #[test]
+    fn rejects_flow_label_out_of_range() {
+        let mut stream = base_stream();
+        stream.ip_version = Some(6);
+        stream.flow_label = Some(1 << 20);
+        let mut setting = base_setting();
+        setting.ip = None;
+        setting.ipv6 = Some(Ipv6Header { ipv6_src: "::1".to_string(), ipv6_dst: "::2".to_string() });
+
+        assert!(validate_request(&[stream], &[setting], &GenerationMode::Cbr, false).is_err());
+    }
+
+    #[test]
+    fn rejects_dsfield_on_headerless_srv6_stream() {
+        let mut stream = base_stream();
+        stream.ip_version = None;
+        stream.encapsulation = vec![Encapsulation::SRv6];
+        stream.number_of_srv6_sids = Some(1);
+        stream.srv6_ip_tunneling = Some(false);
+        stream.dscp = Some(10);
+
+        let mut setting = base_setting();
+        setting.ip = None;
+        setting.sid_list = Some(vec!["::1".to_string()]);
+
+        assert!(validate_request(&[stream], &[setting], &GenerationMode::Cbr, true).is_err());
+    }
+
+    #[test]
+    fn accepts_headerless_srv6_stream_without_dsfield() {
+        let mut stream = base_stream();
+        stream.ip_version = None;
+        stream.encapsulation = vec![Encapsulation::SRv6];
+        stream.number_of_srv6_sids = Some(1);
+        stream.srv6_ip_tunneling = Some(false);
+
+        let mut setting = base_setting();
+        setting.ip = None;
+        setting.sid_list = Some(vec!["::1".to_string()]);
+
+        assert!(validate_request(&[stream], &[setting], &GenerationMode::Cbr, true).is_ok());
+    }
+
+    #[test]
+    fn rejects_stacked_encapsulation_missing_inner_layer_settings() {
+        let mut stream = base_stream();
+        stream.encapsulation = vec![Encapsulation::Vlan, Encapsulation::Mpls];
+        stream.number_of_lse = Some(2);
+
+        let mut setting = base_setting();
+        setting.vlan = Some(VlanHeader { pcp: 0, dei: 0, vlan_id: 100, inner_pcp: 0, inner_dei: 0, inner_vlan_id: 0 });
+        // MPLS stack missing even though the Vlan layer is satisfied.
+
+        assert!(validate_request(&[stream], &[setting], &GenerationMode::Cbr, false).is_err());
+    }
+
+    #[test]
+    fn accepts_fully_configured_stacked_encapsulation() {
+        let mut stream = base_stream();
+        stream.encapsulation = vec![Encapsulation::Vlan, Encapsulation::Mpls];
+        stream.number_of_lse = Some(2);
+
+        let mut setting = base_setting();
+        setting.vlan = Some(VlanHeader { pcp: 0, dei: 0, vlan_id: 100, inner_pcp: 0, inner_dei: 0, inner_vlan_id: 0 });
+        setting.mpls_stack = Some(vec![
+            MplsHeader { label: 100, tc: 0, ttl: 64 },
+            MplsHeader { label: 200, tc: 0, ttl: 64 },
+        ]);
+
+        assert!(validate_request(&[stream], &[setting], &GenerationMode::Cbr, false).is_ok());
+    }
+
+    #[test]
+    fn accepts_vxlan_stacked_over_srv6_with_unrelated_second_stream() {
+        // Stream #1 stacks VxLAN on top of an SRv6-tunneled inner IP header.
+        let mut stream1 = base_stream();
+        stream1.encapsulation = vec![Encapsulation::SRv6];
+        stream1.number_of_srv6_sids = Some(1);
+        stream1.vxlan = true;
+
+        let mut setting1 = base_setting();
+        setting1.sid_list = Some(vec!["::1".to_string()]);
+        setting1.vxlan = Some(vxlan_setting(false));
+
+        // Stream #2 is an unrelated plain stream on a different port; it must not
+        // interfere with stream #1's encapsulation/VxLAN validation.
+        let mut stream2 = base_stream();
+        stream2.stream_id = 2;
+
+        let mut setting2 = base_setting();
+        setting2.stream_id = 2;
+        setting2.port = 1;
+
+        assert!(validate_request(&[stream1, stream2], &[setting1, setting2], &GenerationMode::Cbr, true).is_ok());
+    }
+
+    fn vxlan_setting(ipv6: bool) -> VxlanSetting {
+        VxlanSetting {
+            eth_src: "00:00:00:00:00:01".to_string(),
+            eth_dst: "00:00:00:00:00:02".to_string(),
+            ip_src: if ipv6 { None } else { Some("10.0.0.1".to_string()) },
+            ip_dst: if ipv6 { None } else { Some("10.0.0.2".to_string()) },
+            ipv6_src: if ipv6 { Some("::1".to_string()) } else { None },
+            ipv6_dst: if ipv6 { Some("::2".to_string()) } else { None },
+            udp_source: 4789,
+            vni: 100,
+        }
+    }
+
+    fn trtcm_profile() -> TrtcmProfile {
+        TrtcmProfile {
+            cir: 1_000,
+            cbs: 1_000,
+            pir: 2_000,
+            pbs: 2_000,
+            drop_on_red: false,
+            yellow_dscp: None,
+            yellow_pcp: None,
+        }
+    }
+
+    #[test]
+    fn rejects_trtcm_profile_with_pir_below_cir() {
+        let mut stream = base_stream();
+        let mut profile = trtcm_profile();
+        profile.pir = profile.cir - 1;
+        stream.trtcm = Some(profile);
+
+        assert!(validate_request(&[stream], &[base_setting()], &GenerationMode::TrTCM, false).is_err());
+    }
+
+    #[test]
+    fn rejects_trtcm_profile_with_yellow_pcp_out_of_range() {
+        let mut stream = base_stream();
+        let mut profile = trtcm_profile();
+        profile.yellow_pcp = Some(8);
+        stream.trtcm = Some(profile);
+
+        assert!(validate_request(&[stream], &[base_setting()], &GenerationMode::TrTCM, false).is_err());
+    }
+
+    #[test]
+    fn accepts_valid_trtcm_profile() {
+        let mut stream = base_stream();
+        let mut profile = trtcm_profile();
+        profile.yellow_dscp = Some(10);
+        profile.yellow_pcp = Some(3);
+        stream.trtcm = Some(profile);
+
+        assert!(validate_request(&[stream], &[base_setting()], &GenerationMode::TrTCM, false).is_ok());
+    }
+
+    #[test]
+    fn rejects_duplicate_replica_ports() {
+        let stream = base_stream();
+        let mut setting = base_setting();
+        setting.replicas = vec![
+            ReplicaSetting { port: 1, eth_dst: Some("00:00:00:00:00:01".to_string()), vlan: None, mpls_stack: None, sid_list: None },
+            ReplicaSetting { port: 1, eth_dst: Some("00:00:00:00:00:02".to_string()), vlan: None, mpls_stack: None, sid_list: None },
+        ];
+
+        assert!(validate_request(&[stream], &[setting], &GenerationMode::Cbr, false).is_err());
+    }
+
+    #[test]
+    fn rejects_replica_port_equal_to_primary_port() {
+        let stream = base_stream();
+        let mut setting = base_setting();
+        setting.replicas = vec![
+            ReplicaSetting { port: setting.port, eth_dst: Some("00:00:00:00:00:01".to_string()), vlan: None, mpls_stack: None, sid_list: None },
+        ];
+
+        assert!(validate_request(&[stream], &[setting], &GenerationMode::Cbr, false).is_err());
+    }
+
+    #[test]
+    fn accepts_replica_on_distinct_port() {
+        let stream = base_stream();
+        let mut setting = base_setting();
+        setting.replicas = vec![
+            ReplicaSetting { port: setting.port + 1, eth_dst: Some("00:00:00:00:00:01".to_string()), vlan: None, mpls_stack: None, sid_list: None },
+        ];
+
+        assert!(validate_request(&[stream], &[setting], &GenerationMode::Cbr, false).is_ok());
+    }
+
+    #[test]
+    fn rejects_mpls_replica_missing_label_stack_rewrite() {
+        let mut stream = base_stream();
+        stream.encapsulation = vec![Encapsulation::Mpls];
+        stream.number_of_lse = Some(1);
+
+        let mut setting = base_setting();
+        setting.mpls_stack = Some(vec![MplsHeader { label: 100, tc: 0, ttl: 64 }]);
+        setting.replicas = vec![
+            ReplicaSetting { port: setting.port + 1, eth_dst: Some("00:00:00:00:00:01".to_string()), vlan: None, mpls_stack: None, sid_list: None },
+        ];
+
+        assert!(validate_request(&[stream], &[setting], &GenerationMode::Cbr, false).is_err());
+    }
+
+    #[test]
+    fn accepts_mpls_replica_with_matching_label_stack_rewrite() {
+        let mut stream = base_stream();
+        stream.encapsulation = vec![Encapsulation::Mpls];
+        stream.number_of_lse = Some(1);
+
+        let mut setting = base_setting();
+        setting.mpls_stack = Some(vec![MplsHeader { label: 100, tc: 0, ttl: 64 }]);
+        let replica_port = setting.port + 1;
+        setting.replicas = vec![
+            ReplicaSetting {
+                port: replica_port,
+                eth_dst: Some("00:00:00:00:00:01".to_string()),
+                vlan: None,
+                mpls_stack: Some(vec![MplsHeader { label: 200, tc: 0, ttl: 64 }]),
+                sid_list: None,
+            },
+        ];
+
+        assert!(validate_request(&[stream], &[setting], &GenerationMode::Cbr, false).is_ok());
+    }
+
+    #[test]
+    fn rejects_srv6_replica_missing_sid_list_rewrite() {
+        let mut stream = base_stream();
+        stream.ip_version = None;
+        stream.encapsulation = vec![Encapsulation::SRv6];
+        stream.number_of_srv6_sids = Some(1);
+        stream.srv6_ip_tunneling = Some(false);
+
+        let mut setting = base_setting();
+        setting.ip = None;
+        setting.sid_list = Some(vec!["::1".to_string()]);
+        let replica_port = setting.port + 1;
+        setting.replicas = vec![
+            ReplicaSetting { port: replica_port, eth_dst: Some("00:00:00:00:00:01".to_string()), vlan: None, mpls_stack: None, sid_list: None },
+        ];
+
+        assert!(validate_request(&[stream], &[setting], &GenerationMode::Cbr, true).is_err());
+    }
+
+    #[test]
+    fn rejects_vxlanv6_missing_ipv6_outer_addresses() {
+        let mut stream = base_stream();
+        stream.vxlan = true;
+        stream.vxlan_outer_ipv6 = true;
+
+        let mut setting = base_setting();
+        setting.vxlan = Some(vxlan_setting(false));
+
+        assert!(validate_request(&[stream], &[setting], &GenerationMode::Cbr, false).is_err());
+    }
+
+    #[test]
+    fn accepts_vxlanv6_with_ipv6_outer_addresses() {
+        let mut stream = base_stream();
+        stream.vxlan = true;
+        stream.vxlan_outer_ipv6 = true;
+
+        let mut setting = base_setting();
+        setting.vxlan = Some(vxlan_setting(true));
+
+        assert!(validate_request(&[stream], &[setting], &GenerationMode::Cbr, false).is_ok());
+    }
+
+    #[test]
+    fn buffer_size_check_accounts_for_vxlanv6_outer_overhead() {
+        // Large enough that a 20-byte-larger (IPv6) outer tips it over MAX_BUFFER_SIZE,
+        // while the IPv4 outer still fits.
+        let frame_size = MAX_BUFFER_SIZE - 60;
+
+        let mut ipv4_stream = base_stream();
+        ipv4_stream.frame_size = frame_size;
+        ipv4_stream.vxlan = true;
+        let mut ipv4_setting = base_setting();
+        ipv4_setting.vxlan = Some(vxlan_setting(false));
+        assert!(validate_request(&[ipv4_stream], &[ipv4_setting], &GenerationMode::Cbr, false).is_ok());
+
+        let mut ipv6_stream = base_stream();
+        ipv6_stream.frame_size = frame_size;
+        ipv6_stream.vxlan = true;
+        ipv6_stream.vxlan_outer_ipv6 = true;
+        let mut ipv6_setting = base_setting();
+        ipv6_setting.vxlan = Some(vxlan_setting(true));
+        assert!(validate_request(&[ipv6_stream], &[ipv6_setting], &GenerationMode::Cbr, false).is_err());
+    }
+
+    #[test]
+    fn vxlan_checks_do_not_cross_streams() {
+        // Stream #1 is plain (non-VxLAN); stream #2 is VxLAN with its own valid setting.
+        // Stream #1's setting (vxlan: None) must not be checked against stream #2.
+        let stream1 = base_stream();
+        let setting1 = base_setting();
+
+        let mut stream2 = base_stream();
+        stream2.stream_id = 2;
+        stream2.vxlan = true;
+
+        let mut setting2 = base_setting();
+        setting2.stream_id = 2;
+        setting2.port = 1;
+        setting2.vxlan = Some(vxlan_setting(false));
+
+        assert!(validate_request(&[stream1, stream2], &[setting1, setting2], &GenerationMode::Cbr, false).is_ok());
+    }
+
+    #[test]
+    fn vxlanv6_outer_family_check_does_not_cross_streams() {
+        // Stream #1 is VXLANv6 with a valid IPv6 outer on its own setting; stream #2 is a
+        // plain IPv4 VxLAN stream on a different port. Stream #1 must not be validated
+        // against stream #2's IPv4-only VxlanSetting.
+        let mut stream1 = base_stream();
+        stream1.vxlan = true;
+        stream1.vxlan_outer_ipv6 = true;
+
+        let mut setting1 = base_setting();
+        setting1.vxlan = Some(vxlan_setting(true));
+
+        let mut stream2 = base_stream();
+        stream2.stream_id = 2;
+        stream2.vxlan = true;
+
+        let mut setting2 = base_setting();
+        setting2.stream_id = 2;
+        setting2.port = 1;
+        setting2.vxlan = Some(vxlan_setting(false));
+
+        assert!(validate_request(&[stream1, stream2], &[setting1, setting2], &GenerationMode::Cbr, false).is_ok());
+    }
 }
\ No newline at end of file
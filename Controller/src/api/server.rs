@@ -0,0 +1,33 @@
+/* Copyright 2022-present University of Tuebingen, Chair of Communication Networks
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/*
+ * Steffen Lindner (steffen.lindner@uni-tuebingen.de)
+ */
+
+use serde::Serialize;
+
+/// Error that is returned to the REST client if a request could not be fulfilled.
+#[derive(Debug, Clone, Serialize)]
+pub struct Error {
+    pub message: String,
+}
+
+impl Error {
+    /// Creates a new error with the given human-readable message.
+    pub fn new<S: Into<String>>(message: S) -> Error {
+        Error { message: message.into() }
+    }
+}
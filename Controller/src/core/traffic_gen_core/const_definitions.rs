@@ -0,0 +1,33 @@
+/* Copyright 2022-present University of Tuebingen, Chair of Communication Networks
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/*
+ * Steffen Lindner (steffen.lindner@uni-tuebingen.de)
+ */
+
+/// Maximal number of MPLS labels that fit in the packet generation buffer.
+pub const MAX_NUM_MPLS_LABEL: u32 = 15;
+
+/// Maximal number of SRv6 segments (SIDs) supported on Tofino2.
+pub const MAX_NUM_SRV6_SIDS: u32 = 10;
+
+/// Maximal summed packet size (in bytes) that fits into the packet generation buffer.
+pub const MAX_BUFFER_SIZE: u32 = 10240;
+
+/// Maximal sending rate in Gbit/s on Tofino1.
+pub const TG_MAX_RATE: f32 = 100f32;
+
+/// Maximal sending rate in Gbit/s on Tofino2.
+pub const TG_MAX_RATE_TF2: f32 = 400f32;
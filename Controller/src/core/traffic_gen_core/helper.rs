@@ -0,0 +1,58 @@
+/* Copyright 2022-present University of Tuebingen, Chair of Communication Networks
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/*
+ * Steffen Lindner (steffen.lindner@uni-tuebingen.de)
+ */
+
+use crate::core::traffic_gen_core::types::{Encapsulation, Stream};
+
+/// Bytes added by the outer Ethernet + IPv4 + UDP + VxLAN headers of a VxLAN encapsulation.
+const VXLAN_OVERHEAD: u32 = 14 + 20 + 8 + 8;
+
+/// Additional bytes of an IPv6 outer header compared to an IPv4 outer (40 vs 20 bytes).
+const IPV6_OUTER_EXTRA: u32 = 20;
+
+/// Bytes added by the SRv6 routing header: the IPv6 base header plus the 8-byte SRH base.
+const SRV6_BASE_OVERHEAD: u32 = 40 + 8;
+
+/// Computes the per-packet overhead (in bytes) added by the encapsulation of a stream.
+/// This is added on top of the configured frame size for the rate and buffer-size checks.
+/// The encapsulation is a stack of layers (outer to inner), so the overhead is the sum
+/// over all layers.
+pub fn calculate_overhead(stream: &Stream) -> u32 {
+    let mut overhead = 0;
+
+    for encapsulation in stream.encapsulation.iter() {
+        overhead += match encapsulation {
+            Encapsulation::Vlan => 4,
+            Encapsulation::QinQ => 8,
+            Encapsulation::Mpls => 4 * stream.number_of_lse.unwrap_or(0),
+            Encapsulation::SRv6 => SRV6_BASE_OVERHEAD + 16 * stream.number_of_srv6_sids.unwrap_or(0),
+            _ => 0,
+        };
+    }
+
+    if stream.vxlan {
+        overhead += VXLAN_OVERHEAD;
+
+        // A VXLANv6 outer carries a 40-byte IPv6 header instead of the 20-byte IPv4 one.
+        if stream.vxlan_outer_ipv6 {
+            overhead += IPV6_OUTER_EXTRA;
+        }
+    }
+
+    overhead
+}
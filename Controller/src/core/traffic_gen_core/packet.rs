@@ -0,0 +1,214 @@
+/* Copyright 2022-present University of Tuebingen, Chair of Communication Networks
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/*
+ * Steffen Lindner (steffen.lindner@uni-tuebingen.de)
+ */
+
+use crate::core::traffic_gen_core::types::{Encapsulation, ReplicaSetting, Stream, StreamSetting};
+use crate::core::traffic_gen_core::trtcm::{Color, TwoRateThreeColorMarker};
+
+/// Writes the IPv4 type-of-service byte and TTL into the IPv4 header template.
+/// The DSCP/ECN bits and the TTL are taken from the stream configuration and
+/// default to best-effort (0) / 64 hops when the stream does not override them.
+pub fn apply_ipv4_dsfield(header: &mut [u8], stream: &Stream) {
+    // byte 1 (offset 1): DSCP (upper 6 bits) + ECN (lower 2 bits)
+    let dscp = stream.dscp.unwrap_or(0);
+    let ecn = stream.ecn.unwrap_or(0);
+    header[1] = (dscp << 2) | (ecn & 0b11);
+
+    // byte 8 (offset 8): time-to-live
+    header[8] = stream.ip_ttl.unwrap_or(64);
+}
+
+/// Writes the IPv6 traffic-class, flow-label and hop-limit into the IPv6 header template.
+/// The traffic-class carries the same DSCP/ECN bits as the IPv4 path; the 20-bit flow-label
+/// and hop-limit default to 0 / 64 when the stream does not override them.
+pub fn apply_ipv6_dsfield(header: &mut [u8], stream: &Stream) {
+    let dscp = stream.dscp.unwrap_or(0);
+    let ecn = stream.ecn.unwrap_or(0);
+    let traffic_class = ((dscp << 2) | (ecn & 0b11)) as u32;
+    let flow_label = stream.flow_label.unwrap_or(0) & 0xF_FFFF;
+
+    // first 32-bit word: version (4 bit) | traffic class (8 bit) | flow label (20 bit)
+    let first_word = (6u32 << 28) | (traffic_class << 20) | flow_label;
+    header[0..4].copy_from_slice(&first_word.to_be_bytes());
+
+    // byte 7 (offset 7): hop limit
+    header[7] = stream.hop_limit.unwrap_or(64);
+}
+
+/// Builds the inner IP header template of a stream and emits its ds-field, TTL/hop-limit
+/// and (for IPv6) flow-label from the stream configuration. This is the header-build path
+/// the ds-field writers are threaded into. Returns an empty template for a stream that
+/// carries no IP payload at all (`ip_version` is `None`).
+pub fn build_ip_header(stream: &Stream) -> Vec<u8> {
+    match stream.ip_version {
+        Some(6) => {
+            let mut header = vec![0u8; 40];
+            apply_ipv6_dsfield(&mut header, stream);
+            header
+        }
+        Some(_) => {
+            let mut header = vec![0u8; 20];
+            header[0] = 0x45; // version 4, IHL 5
+            apply_ipv4_dsfield(&mut header, stream);
+            header
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Writes the DSCP/TTL of the outer VxLAN IP header. These are configured independently
+/// of the inner IP header and fall back to best-effort / 64 hops. The byte layout differs
+/// between an IPv4 outer (ToS at offset 1, TTL at offset 8) and an IPv6 (VXLANv6) outer
+/// (traffic-class in the first word, hop-limit at offset 7).
+pub fn apply_vxlan_outer_dsfield(header: &mut [u8], stream: &Stream) {
+    let dscp = stream.vxlan_dscp.unwrap_or(0);
+    let ttl = stream.vxlan_ttl.unwrap_or(64);
+
+    if stream.vxlan_outer_ipv6 {
+        // first 32-bit word: version (4 bit) | traffic class (8 bit) | flow label (20 bit)
+        let traffic_class = (dscp as u32) << 2;
+        let first_word = (6u32 << 28) | (traffic_class << 20);
+        header[0..4].copy_from_slice(&first_word.to_be_bytes());
+
+        // byte 7 (offset 7): hop limit
+        header[7] = ttl;
+    } else {
+        header[1] = dscp << 2;
+        header[8] = ttl;
+    }
+}
+
+/// Builds the outer VxLAN IP header template (IPv4 or IPv6 depending on `vxlan_outer_ipv6`)
+/// and emits its ds-field/TTL so the outer header is sized and marked correctly.
+pub fn build_vxlan_outer_ip(stream: &Stream) -> Vec<u8> {
+    let mut header = if stream.vxlan_outer_ipv6 {
+        vec![0u8; 40]
+    } else {
+        let mut header = vec![0u8; 20];
+        header[0] = 0x45; // version 4, IHL 5
+        header
+    };
+
+    apply_vxlan_outer_dsfield(&mut header, stream);
+    header
+}
+
+/// Parses a colon-separated MAC address into its six bytes.
+fn parse_mac(mac: &str) -> Option<[u8; 6]> {
+    let mut bytes = [0u8; 6];
+    let mut parts = mac.split(':');
+
+    for byte in bytes.iter_mut() {
+        *byte = u8::from_str_radix(parts.next()?, 16).ok()?;
+    }
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some(bytes)
+}
+
+/// Builds the 2-byte 802.1Q tag control information: PCP (upper 3 bits), DEI (next bit)
+/// and VLAN id (lower 12 bits).
+fn build_tci(pcp: u8, dei: u8, vlan_id: u16) -> [u8; 2] {
+    let tci = ((pcp as u16 & 0x7) << 13) | ((dei as u16 & 0x1) << 12) | (vlan_id & 0x0FFF);
+    tci.to_be_bytes()
+}
+
+/// Applies the per-replica rewrites of a multicast copy to the Ethernet header template:
+/// the destination MAC and, for a VLAN-tagged replica, the VLAN tag(s). The Ethernet
+/// destination is located at offset 0; the outer TCI sits two bytes after the 0x8100 TPID
+/// at offset 12. For a QinQ stream a second (inner) tag follows directly after the outer
+/// one, its TCI at offset 18..20.
+pub fn apply_replica_rewrite(ethernet: &mut [u8], stream: &Stream, replica: &ReplicaSetting) {
+    if let Some(mac) = replica.eth_dst.as_ref().and_then(|m| parse_mac(m)) {
+        ethernet[0..6].copy_from_slice(&mac);
+    }
+
+    if let Some(vlan) = replica.vlan.as_ref() {
+        ethernet[14..16].copy_from_slice(&build_tci(vlan.pcp, vlan.dei, vlan.vlan_id));
+
+        if stream.encapsulation.contains(&Encapsulation::QinQ) {
+            ethernet[18..20].copy_from_slice(&build_tci(vlan.inner_pcp, vlan.inner_dei, vlan.inner_vlan_id));
+        }
+    }
+}
+
+/// Overwrites the DSCP bits of an already-built inner IP header, preserving the ECN bits
+/// (IPv4) / flow-label (IPv6). Used to re-color a packet marked yellow by the trTCM shaper.
+fn set_dscp(ip_header: &mut [u8], stream: &Stream, dscp: u8) {
+    if stream.ip_version == Some(6) {
+        let ecn = stream.ecn.unwrap_or(0) as u32;
+        let traffic_class = ((dscp as u32) << 2) | (ecn & 0b11);
+        let first_word = u32::from_be_bytes([ip_header[0], ip_header[1], ip_header[2], ip_header[3]]);
+        // clear the 8 traffic-class bits (20..28), keep version (28..32) and flow label (0..20)
+        let first_word = (first_word & 0xF00F_FFFF) | (traffic_class << 20);
+        ip_header[0..4].copy_from_slice(&first_word.to_be_bytes());
+    } else {
+        let ecn = stream.ecn.unwrap_or(0);
+        ip_header[1] = (dscp << 2) | (ecn & 0b11);
+    }
+}
+
+/// Overwrites the PCP bits of an already-built 802.1Q tag control information, preserving
+/// the DEI bit and VLAN id. Used to re-color a packet marked yellow by the trTCM shaper.
+fn set_pcp(vlan_tci: &mut [u8], pcp: u8) {
+    let tci = u16::from_be_bytes([vlan_tci[0], vlan_tci[1]]);
+    let tci = (tci & 0x1FFF) | ((pcp as u16 & 0x7) << 13);
+    vlan_tci[0..2].copy_from_slice(&tci.to_be_bytes());
+}
+
+/// Shapes a single packet of a trTCM stream: refills the token buckets for the elapsed
+/// time, marks the packet green/yellow/red, re-colors yellow packets with the configured
+/// yellow DSCP/PCP, and returns whether the packet should be emitted. Red packets are
+/// dropped when the profile requests it. `vlan_tci` is the outer 802.1Q TCI of the packet
+/// template, when the stream is VLAN-tagged.
+pub fn shape_packet(marker: &mut TwoRateThreeColorMarker, stream: &Stream, ip_header: &mut [u8], vlan_tci: Option<&mut [u8]>, size: u64, elapsed_seconds: f64) -> bool {
+    let profile = match stream.trtcm.as_ref() {
+        Some(profile) => profile,
+        None => return true,
+    };
+
+    marker.refill(elapsed_seconds);
+
+    match marker.mark(size) {
+        Color::Green => true,
+        Color::Yellow => {
+            if let Some(dscp) = profile.yellow_dscp {
+                set_dscp(ip_header, stream, dscp);
+            }
+            if let (Some(pcp), Some(tci)) = (profile.yellow_pcp, vlan_tci) {
+                set_pcp(tci, pcp);
+            }
+            true
+        }
+        Color::Red => !profile.drop_on_red,
+    }
+}
+
+/// Builds one packet copy per multicast replica from the stream's base Ethernet template,
+/// applying each replica's destination-MAC/VLAN rewrite. Returns the `(egress_port, packet)`
+/// pairs that the generation path emits in addition to the stream's primary port.
+pub fn build_replica_packets(base_ethernet: &[u8], stream: &Stream, setting: &StreamSetting) -> Vec<(u32, Vec<u8>)> {
+    setting.replicas.iter().map(|replica| {
+        let mut packet = base_ethernet.to_vec();
+        apply_replica_rewrite(&mut packet, stream, replica);
+        (replica.port, packet)
+    }).collect()
+}
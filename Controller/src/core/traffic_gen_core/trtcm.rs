@@ -0,0 +1,138 @@
+/* Copyright 2022-present University of Tuebingen, Chair of Communication Networks
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/*
+ * Steffen Lindner (steffen.lindner@uni-tuebingen.de)
+ */
+
+use crate::core::traffic_gen_core::types::{Stream, TrtcmProfile};
+
+/// Color assigned to a packet by the two-rate three-color marker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Green,
+    Yellow,
+    Red,
+}
+
+/// Two-rate three-color marker (RFC 2698). Maintains a committed (C) and a peak (P)
+/// token bucket, refilled at CIR/PIR up to CBS/PBS. For a packet of `size` bytes the
+/// marker consumes from the buckets and returns the resulting color.
+pub struct TwoRateThreeColorMarker {
+    cir: u64,
+    cbs: u64,
+    pir: u64,
+    pbs: u64,
+    /// Committed token bucket, in bytes.
+    tc: u64,
+    /// Peak token bucket, in bytes.
+    tp: u64,
+}
+
+impl TwoRateThreeColorMarker {
+    /// Creates a marker from a profile with both buckets initially filled to their burst size.
+    pub fn new(profile: &TrtcmProfile) -> TwoRateThreeColorMarker {
+        TwoRateThreeColorMarker {
+            cir: profile.cir,
+            cbs: profile.cbs,
+            pir: profile.pir,
+            pbs: profile.pbs,
+            tc: profile.cbs,
+            tp: profile.pbs,
+        }
+    }
+
+    /// Creates a marker for a stream running in `GenerationMode::TrTCM`, or `None` when the
+    /// stream carries no trTCM profile.
+    pub fn from_stream(stream: &Stream) -> Option<TwoRateThreeColorMarker> {
+        stream.trtcm.as_ref().map(TwoRateThreeColorMarker::new)
+    }
+
+    /// Refills both buckets according to the elapsed time (in seconds) since the last packet,
+    /// capping them at CBS/PBS. Rates are given in bits/s, buckets in bytes.
+    pub fn refill(&mut self, elapsed_seconds: f64) {
+        let tc = (self.cir as f64 / 8f64 * elapsed_seconds) as u64;
+        let tp = (self.pir as f64 / 8f64 * elapsed_seconds) as u64;
+
+        self.tc = (self.tc + tc).min(self.cbs);
+        self.tp = (self.tp + tp).min(self.pbs);
+    }
+
+    /// Marks a packet of `size` bytes: red if the peak bucket is exhausted, yellow if only
+    /// the committed bucket is exhausted, green otherwise. Consumes tokens accordingly.
+    pub fn mark(&mut self, size: u64) -> Color {
+        if self.tp < size {
+            Color::Red
+        } else if self.tc < size {
+            self.tp -= size;
+            Color::Yellow
+        } else {
+            self.tc -= size;
+            self.tp -= size;
+            Color::Green
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile() -> TrtcmProfile {
+        TrtcmProfile {
+            cir: 8_000,
+            cbs: 1_000,
+            pir: 16_000,
+            pbs: 2_000,
+            drop_on_red: false,
+            yellow_dscp: None,
+            yellow_pcp: None,
+        }
+    }
+
+    #[test]
+    fn marks_green_yellow_red_at_boundaries() {
+        let profile = profile();
+        let mut marker = TwoRateThreeColorMarker::new(&profile);
+
+        // both buckets full -> green (tc: 1000->500, tp: 2000->1500)
+        assert_eq!(marker.mark(500), Color::Green);
+
+        // committed bucket exhausted (500 < 600), peak still sufficient -> yellow (tp: 1500->900)
+        assert_eq!(marker.mark(600), Color::Yellow);
+
+        // peak bucket exhausted (900 < 1000) -> red
+        assert_eq!(marker.mark(1000), Color::Red);
+    }
+
+    #[test]
+    fn refill_caps_at_burst_size() {
+        let profile = profile();
+        let mut marker = TwoRateThreeColorMarker::new(&profile);
+
+        // drain both buckets down (tc: 0, tp: 1000)
+        assert_eq!(marker.mark(1000), Color::Green);
+
+        // a huge elapsed time would add far more tokens than the burst sizes allow,
+        // but both buckets must be capped at CBS/PBS
+        marker.refill(1_000_000f64);
+
+        // committed burst fits again -> green (tc: 0, tp: 1000)
+        assert_eq!(marker.mark(1000), Color::Green);
+
+        // committed bucket now empty, peak bucket still holds 1000 tokens -> yellow
+        assert_eq!(marker.mark(1000), Color::Yellow);
+    }
+}
@@ -0,0 +1,210 @@
+/* Copyright 2022-present University of Tuebingen, Chair of Communication Networks
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+/*
+ * Steffen Lindner (steffen.lindner@uni-tuebingen.de)
+ */
+
+use serde::{Deserialize, Serialize};
+
+/// Encapsulation that is pushed on top of the generated Ethernet/IP packet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Encapsulation {
+    #[default]
+    None,
+    Vlan,
+    QinQ,
+    Mpls,
+    SRv6,
+    Vxlan,
+}
+
+/// Mode in which the traffic generator emits a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GenerationMode {
+    /// Constant bit rate, rate is given in Gbit/s.
+    Cbr,
+    /// Rate is given in million packets per second.
+    Mpps,
+    /// Poisson distributed inter-arrival times.
+    Poisson,
+    /// Only analyze incoming traffic, do not generate.
+    Analyze,
+    /// Two-rate three-color marked traffic following a committed/peak token-bucket profile.
+    TrTCM,
+}
+
+/// Two-rate three-color (trTCM) token-bucket profile of a stream.
+/// `cir`/`pir` are rates in bits/s, `cbs`/`pbs` are burst sizes in bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrtcmProfile {
+    pub cir: u64,
+    pub cbs: u64,
+    pub pir: u64,
+    pub pbs: u64,
+    /// Drop packets marked red instead of forwarding them.
+    #[serde(default)]
+    pub drop_on_red: bool,
+    /// DSCP that is set on packets marked yellow.
+    #[serde(default)]
+    pub yellow_dscp: Option<u8>,
+    /// VLAN PCP that is set on packets marked yellow.
+    #[serde(default)]
+    pub yellow_pcp: Option<u8>,
+}
+
+/// A single VLAN tag.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VlanHeader {
+    pub pcp: u8,
+    pub dei: u8,
+    pub vlan_id: u16,
+    /// Inner tag for QinQ double tagging.
+    #[serde(default)]
+    pub inner_pcp: u8,
+    #[serde(default)]
+    pub inner_dei: u8,
+    #[serde(default)]
+    pub inner_vlan_id: u16,
+}
+
+/// A single MPLS label stack entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MplsHeader {
+    pub label: u32,
+    pub tc: u8,
+    pub ttl: u8,
+}
+
+/// IPv4 source/destination of the generated packet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ipv4Header {
+    pub ip_src: String,
+    pub ip_dst: String,
+}
+
+/// IPv6 source/destination of the generated packet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ipv6Header {
+    pub ipv6_src: String,
+    pub ipv6_dst: String,
+}
+
+/// Outer VxLAN header settings of a VxLAN stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VxlanSetting {
+    pub eth_src: String,
+    pub eth_dst: String,
+    /// Outer IPv4 source/destination (`None` for a VXLANv6 outer).
+    #[serde(default)]
+    pub ip_src: Option<String>,
+    #[serde(default)]
+    pub ip_dst: Option<String>,
+    /// Outer IPv6 source/destination (`None` for an IPv4 outer).
+    #[serde(default)]
+    pub ipv6_src: Option<String>,
+    #[serde(default)]
+    pub ipv6_dst: Option<String>,
+    pub udp_source: u16,
+    pub vni: u32,
+}
+
+/// A single multicast replica: a copy of the stream emitted to an additional egress
+/// port with its own rewrite of every header the stream's encapsulation stack needs
+/// (destination MAC, and VLAN tag / MPLS stack / SID list where applicable).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicaSetting {
+    pub port: u32,
+    #[serde(default)]
+    pub eth_dst: Option<String>,
+    #[serde(default)]
+    pub vlan: Option<VlanHeader>,
+    #[serde(default)]
+    pub mpls_stack: Option<Vec<MplsHeader>>,
+    #[serde(default)]
+    pub sid_list: Option<Vec<String>>,
+}
+
+/// A stream describes the packet that is generated together with its rate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stream {
+    pub stream_id: u8,
+    pub frame_size: u32,
+    pub traffic_rate: f32,
+    /// Ordered encapsulation stack, outer to inner. Empty means no encapsulation.
+    #[serde(default)]
+    pub encapsulation: Vec<Encapsulation>,
+    #[serde(default)]
+    pub number_of_lse: Option<u32>,
+    #[serde(default)]
+    pub number_of_srv6_sids: Option<u32>,
+    #[serde(default)]
+    pub srv6_ip_tunneling: Option<bool>,
+    #[serde(default)]
+    pub ip_version: Option<u8>,
+    #[serde(default)]
+    pub vxlan: bool,
+    /// Differentiated-services code point of the (inner) IP header.
+    #[serde(default)]
+    pub dscp: Option<u8>,
+    /// Explicit congestion notification bits of the (inner) IP header.
+    #[serde(default)]
+    pub ecn: Option<u8>,
+    /// IPv4 time-to-live of the (inner) IP header.
+    #[serde(default)]
+    pub ip_ttl: Option<u8>,
+    /// IPv6 hop-limit of the (inner) IP header.
+    #[serde(default)]
+    pub hop_limit: Option<u8>,
+    /// 20-bit IPv6 flow-label of the (inner) IP header.
+    #[serde(default)]
+    pub flow_label: Option<u32>,
+    /// DSCP of the outer VxLAN IP header, independent of the inner header.
+    #[serde(default)]
+    pub vxlan_dscp: Option<u8>,
+    /// TTL of the outer VxLAN IP header, independent of the inner header.
+    #[serde(default)]
+    pub vxlan_ttl: Option<u8>,
+    /// Use an IPv6 (VXLANv6) outer header instead of IPv4, independent of the inner version.
+    #[serde(default)]
+    pub vxlan_outer_ipv6: bool,
+    /// Two-rate three-color profile, required when the mode is `GenerationMode::TrTCM`.
+    #[serde(default)]
+    pub trtcm: Option<TrtcmProfile>,
+}
+
+/// Per-port settings of a stream.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamSetting {
+    pub stream_id: u8,
+    pub port: u32,
+    #[serde(default)]
+    pub vlan: Option<VlanHeader>,
+    #[serde(default)]
+    pub mpls_stack: Option<Vec<MplsHeader>>,
+    #[serde(default)]
+    pub sid_list: Option<Vec<String>>,
+    #[serde(default)]
+    pub ip: Option<Ipv4Header>,
+    #[serde(default)]
+    pub ipv6: Option<Ipv6Header>,
+    #[serde(default)]
+    pub vxlan: Option<VxlanSetting>,
+    /// Multicast replicas: additional egress ports this stream is replicated to.
+    #[serde(default)]
+    pub replicas: Vec<ReplicaSetting>,
+    #[serde(default)]
+    pub active: bool,
+}